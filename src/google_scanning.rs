@@ -1,4 +1,5 @@
-//! Collection of tools for scanning Google Suite for secrets. Currently only supports Google Drive.
+//! Collection of tools for scanning Google Suite for secrets. Currently supports Google Drive,
+//! Google Cloud Storage, and Gmail.
 //!
 //! `GoogleScanner` acts as a wrapper around a [`SecretScanner`] object to provide helper functions for
 //! performing scanning against Google Drive files. Relies on the
@@ -62,7 +63,9 @@
 //! );
 //!
 //! // get some initial info about the file
-//! let gdriveinfo = GDriveFileInfo::new("gdrive_file_id", &hub).unwrap();
+//! # use std::collections::HashMap;
+//! let mut path_cache = HashMap::new();
+//! let gdriveinfo = GDriveFileInfo::new("gdrive_file_id", &hub, &mut path_cache).unwrap();
 //!
 //! // Do the scan
 //! let findings = gdrive_scanner.perform_scan(&gdriveinfo, &hub, false);
@@ -77,17 +80,86 @@
 //! [`perform_scan`]: struct.GDriveScanner.html#method.perform_scan
 
 use crate::SecretScanner;
+use base64::{decode_config, URL_SAFE_NO_PAD};
+use calamine::{open_workbook_auto_from_rs, Reader};
+use pdf_extract::extract_text_from_mem;
 use encoding::all::ASCII;
 use encoding::{DecoderTrap, Encoding};
 use google_drive3::{DriveHub, Scope};
+use google_gmail1::{Gmail, MessagePart, Scope as GmailScope};
+use google_storage1::{Scope as StorageScope, Storage};
 use hyper::Client;
 use serde_derive::{Deserialize, Serialize};
 use simple_error::SimpleError;
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::io::Cursor;
 use std::io::Read;
-use std::iter::FromIterator;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tokio::task;
 use yup_oauth2::{Authenticator, DefaultAuthenticatorDelegate, DiskTokenStorage};
 
+/// Convenience alias for the fully-qualified, authenticated `DriveHub` type used throughout this
+/// module, so async code can share one hub across many concurrent downloads via `Arc`.
+pub type AuthenticatedDriveHub =
+    DriveHub<Client, Authenticator<DefaultAuthenticatorDelegate, DiskTokenStorage, Client>>;
+
+/// Default number of Drive file downloads to run concurrently during a folder scan.
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Shared core of the "split into lines, run regex + entropy matches, build one finding per hit"
+/// loop used by every subsystem in this module (`GDriveScanner`, `GCSScanner`, `GmailScanner`).
+/// `make_finding` turns one match (its reason, the decoded strings found, and the decoded
+/// surrounding line) into that subsystem's own finding type.
+fn scan_lines_for_secrets<F, M>(
+    secret_scanner: &SecretScanner,
+    buffer: &[u8],
+    scan_entropy: bool,
+    make_finding: M,
+) -> HashSet<F>
+where
+    F: Eq + std::hash::Hash,
+    M: Fn(&str, Vec<String>, String) -> F,
+{
+    let lines = buffer.split(|x| (*x as char) == '\n');
+    let mut findings: HashSet<F> = HashSet::new();
+    for new_line in lines {
+        let matches_map = secret_scanner.matches(&new_line);
+        for (reason, match_iterator) in matches_map {
+            let mut secrets: Vec<String> = Vec::new();
+            for matchobj in match_iterator {
+                secrets.push(
+                    ASCII
+                        .decode(
+                            &new_line[matchobj.start()..matchobj.end()],
+                            DecoderTrap::Ignore,
+                        )
+                        .unwrap_or_else(|_| "<STRING DECODE ERROR>".parse().unwrap()),
+                );
+            }
+            if !secrets.is_empty() {
+                let diff = ASCII
+                    .decode(&new_line, DecoderTrap::Ignore)
+                    .unwrap_or_else(|_| "<STRING DECODE ERROR>".parse().unwrap());
+                findings.insert(make_finding(&reason, secrets, diff));
+            }
+        }
+
+        if scan_entropy {
+            let ef = SecretScanner::entropy_findings(new_line);
+            if !ef.is_empty() {
+                let diff = ASCII
+                    .decode(&new_line, DecoderTrap::Ignore)
+                    .unwrap_or_else(|_| "<STRING DECODE ERROR>".parse().unwrap());
+                findings.insert(make_finding("Entropy", ef, diff));
+            }
+        }
+    }
+    findings
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
 /// `serde_json` object that represents a single found secret - finding
 ///
@@ -125,14 +197,71 @@ pub struct GDriveScanner {
     pub secret_scanner: SecretScanner,
 }
 
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+/// How to retrieve and interpret the bytes of a Drive file for scanning, chosen from its
+/// `mimeType` by [`export_strategy_for_mime_type`].
+pub enum ExportStrategy {
+    /// Export a Google-native file (Sheet, Doc, Slides) through the Drive export endpoint, as the
+    /// given MIME type.
+    Export(String),
+    /// Download the raw bytes of a non-Google-native file via `alt=media` and scan them as-is.
+    Download,
+    /// Download the raw bytes of an uploaded spreadsheet (`.xlsx`, `.ods`) via `alt=media` and
+    /// extract cell text with `calamine` before scanning.
+    SpreadsheetBinary,
+    /// Download the raw bytes of a PDF via `alt=media` and extract its text layer with
+    /// `pdf_extract` before scanning, since PDF text lives inside compressed content streams and
+    /// can't be scanned as raw bytes.
+    PdfText,
+}
+
+impl Default for ExportStrategy {
+    fn default() -> Self {
+        ExportStrategy::Download
+    }
+}
+
+/// Maps a Drive `mimeType` to the [`ExportStrategy`] used to retrieve its contents, or `None` if
+/// the type isn't supported yet.
+fn export_strategy_for_mime_type(mime_type: &str) -> Option<ExportStrategy> {
+    match mime_type {
+        "application/vnd.google-apps.spreadsheet" => {
+            Some(ExportStrategy::Export("text/csv".to_owned()))
+        }
+        "application/vnd.google-apps.document" => {
+            Some(ExportStrategy::Export("text/plain".to_owned()))
+        }
+        "application/vnd.google-apps.presentation" => {
+            Some(ExportStrategy::Export("text/plain".to_owned()))
+        }
+        "application/pdf" => Some(ExportStrategy::PdfText),
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => {
+            Some(ExportStrategy::SpreadsheetBinary)
+        }
+        "application/vnd.oasis.opendocument.spreadsheet" => {
+            Some(ExportStrategy::SpreadsheetBinary)
+        }
+        // Folders aren't files to download, and shortcuts are resolved to their target's own
+        // mimeType before this function is consulted, so both stay unhandled here.
+        "application/vnd.google-apps.folder" | "application/vnd.google-apps.shortcut" => None,
+        // Other native Google Apps types (Forms, Drawings, Sites, Apps Script, Jamboard, ...)
+        // have no raw byte representation to download.
+        mime_type if mime_type.starts_with("application/vnd.google-apps.") => None,
+        // Anything else is a regular uploaded file (.txt, .json, .env, .pem, .yaml, source code,
+        // already-exported .csv, ...) and can be downloaded and scanned as-is.
+        _ => Some(ExportStrategy::Download),
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
 /// A helper object containing a set of strings describing a Google Drive file.
 ///
 /// ```
-/// # use rusty_hogs::google_scanning::GDriveFileInfo;
+/// # use rusty_hogs::google_scanning::{GDriveFileInfo, ExportStrategy};
 /// let gdfi: GDriveFileInfo = GDriveFileInfo {
 ///   file_id: String::from("GDrive file ID"),
 ///    mime_type: String::from("MIME"),
+///    export_strategy: ExportStrategy::Download,
 ///    modified_time: String::from("context around finding"),
 ///    web_link: String::from("context around finding"),
 ///    parents: Vec::new(),
@@ -143,6 +272,7 @@ pub struct GDriveScanner {
 pub struct GDriveFileInfo {
     pub file_id: String,
     pub mime_type: String,
+    pub export_strategy: ExportStrategy,
     pub modified_time: String,
     pub web_link: String,
     pub parents: Vec<String>,
@@ -150,15 +280,20 @@ pub struct GDriveFileInfo {
     pub path: String,
 }
 
+/// Memoized lookups from Drive file/folder ID to `(name, parent_id)`, shared across a single scan
+/// so resolving the human-readable path of thousands of files doesn't repeatedly re-fetch the
+/// same ancestor folders.
+pub type PathCache = HashMap<String, (String, Option<String>)>;
+
 impl GDriveFileInfo {
 
-    /// Construct a `GDriveFileInfo` object from a Google Drive File ID and an authorized `DriveHub` object
+    /// Construct a `GDriveFileInfo` object from a Google Drive File ID and an authorized `DriveHub` object.
+    /// `path_cache` memoizes id -> name lookups performed while resolving `parents` into a
+    /// human-readable `path`; pass the same cache across an entire folder scan.
     pub fn new(
         file_id: &str,
-        hub: &DriveHub<
-            Client,
-            Authenticator<DefaultAuthenticatorDelegate, DiskTokenStorage, Client>,
-        >,
+        hub: &AuthenticatedDriveHub,
+        path_cache: &mut PathCache,
     ) -> Result<Self, SimpleError> {
         let fields = "kind, id, name, mimeType, webViewLink, modifiedTime, parents";
         let hub_result = hub
@@ -166,6 +301,7 @@ impl GDriveFileInfo {
             .get(file_id)
             .add_scope(Scope::Readonly)
             .param("fields", fields)
+            .param("supportsAllDrives", "true")
             .doit();
         let (_, file_object) = match hub_result {
             Ok(x) => x,
@@ -180,17 +316,31 @@ impl GDriveFileInfo {
         // initialize some variables from the response
         let modified_time = file_object.modified_time.unwrap();
         let web_link = file_object.web_view_link.unwrap();
-        let parents = file_object.parents.unwrap_or_else(Vec::new); //TODO: add code to map from id -> name
+        let parents = file_object.parents.unwrap_or_else(Vec::new);
         let name = file_object.name.unwrap();
-        let path = format!("{}/{}", parents.join("/"), name);
-        let mime_type = match file_object.mime_type.unwrap().as_ref() {
-            "application/vnd.google-apps.spreadsheet" => "text/csv", //TODO: Support application/x-vnd.oasis.opendocument.spreadsheet https://github.com/tafia/calamine
-            "application/vnd.google-apps.document" => "text/plain",
-            u => return Err(SimpleError::new(format!("unknown doc type {}", u))),
+        let path = if parents.is_empty() {
+            name.clone()
+        } else {
+            format!(
+                "{}/{}",
+                resolve_parent_path(&parents, hub, path_cache),
+                name
+            )
+        };
+        let mime_type = file_object.mime_type.unwrap();
+        let export_strategy = match export_strategy_for_mime_type(&mime_type) {
+            Some(strategy) => strategy,
+            None => {
+                return Err(SimpleError::new(format!(
+                    "unsupported file type {}, skipping",
+                    mime_type
+                )))
+            }
         };
         Ok(Self {
             file_id: file_id.to_owned(),
-            mime_type: mime_type.to_owned(),
+            mime_type,
+            export_strategy,
             modified_time,
             web_link,
             parents,
@@ -200,6 +350,53 @@ impl GDriveFileInfo {
     }
 }
 
+/// Walks `parents` upward to the Drive root, resolving each ID to a folder name via `cache`
+/// (fetching and memoizing any ID not already present), and joins the result into a path like
+/// `My Drive/Finance`. Falls back to the raw ID for any parent that can't be fetched, so an
+/// inaccessible ancestor never aborts the whole scan.
+fn resolve_parent_path(
+    parents: &[String],
+    hub: &AuthenticatedDriveHub,
+    cache: &mut PathCache,
+) -> String {
+    let mut segments: Vec<String> = Vec::new();
+    let mut current = parents.first().cloned();
+    while let Some(id) = current {
+        if !cache.contains_key(&id) {
+            let resolved = fetch_parent_info(&id, hub);
+            cache.insert(id.clone(), resolved);
+        }
+        let (name, parent_id) = cache.get(&id).cloned().unwrap();
+        segments.push(name);
+        current = parent_id;
+    }
+    segments.reverse();
+    segments.join("/")
+}
+
+/// Fetches the name and immediate parent of a single Drive ID, falling back to the raw ID if the
+/// parent is inaccessible (e.g. permissions, or it was deleted out from under the scan).
+fn fetch_parent_info(
+    id: &str,
+    hub: &AuthenticatedDriveHub,
+) -> (String, Option<String>) {
+    let hub_result = hub
+        .files()
+        .get(id)
+        .add_scope(Scope::Readonly)
+        .param("fields", "id, name, parents")
+        .param("supportsAllDrives", "true")
+        .doit();
+    match hub_result {
+        Ok((_, file_object)) => {
+            let name = file_object.name.unwrap_or_else(|| id.to_owned());
+            let parent_id = file_object.parents.and_then(|p| p.into_iter().next());
+            (name, parent_id)
+        }
+        Err(_) => (id.to_owned(), None),
+    }
+}
+
 /// Acts as a wrapper around a `SecretScanner` object to provide helper functions for performing
 /// scanning against Google Drive files. Relies on the [`google_drive3`](https://docs.rs/google-drive3/1.0.10+20190620/google_drive3/)
 /// library which provides a wrapper around the Google Drive v3 API.
@@ -213,18 +410,63 @@ impl GDriveScanner {
     pub fn new() -> Self { Self { secret_scanner: SecretScanner::default() } }
 
     /// Takes information about the file, and the DriveHub object, and retrieves the content from
-    /// Google Drive. Expect authorization issues here if you don't have access to the file.
+    /// Google Drive according to its [`ExportStrategy`]. Expect authorization issues here if you
+    /// don't have access to the file.
     fn gdrive_file_contents(
         gdrivefile: &GDriveFileInfo,
-        hub: &DriveHub<
-            Client,
-            Authenticator<DefaultAuthenticatorDelegate, DiskTokenStorage, Client>,
-        >,
+        hub: &AuthenticatedDriveHub,
     ) -> Result<Vec<u8>, SimpleError> {
-        let resp_obj = hub
-            .files()
-            .export(&gdrivefile.file_id, &gdrivefile.mime_type)
-            .doit();
+        match &gdrivefile.export_strategy {
+            ExportStrategy::Export(export_mime_type) => {
+                let resp_obj = hub
+                    .files()
+                    .export(&gdrivefile.file_id, export_mime_type)
+                    .param("supportsAllDrives", "true")
+                    .doit();
+                Self::read_response_body(resp_obj)
+            }
+            ExportStrategy::Download => {
+                let resp_obj = hub
+                    .files()
+                    .get(&gdrivefile.file_id)
+                    .add_scope(Scope::Readonly)
+                    .param("alt", "media")
+                    .param("supportsAllDrives", "true")
+                    .doit();
+                Self::read_response_body(resp_obj.map(|(response, _)| response))
+            }
+            ExportStrategy::SpreadsheetBinary => {
+                let resp_obj = hub
+                    .files()
+                    .get(&gdrivefile.file_id)
+                    .add_scope(Scope::Readonly)
+                    .param("alt", "media")
+                    .param("supportsAllDrives", "true")
+                    .doit();
+                let raw = Self::read_response_body(resp_obj.map(|(response, _)| response))?;
+                Self::extract_spreadsheet_text(&raw)
+            }
+            ExportStrategy::PdfText => {
+                let resp_obj = hub
+                    .files()
+                    .get(&gdrivefile.file_id)
+                    .add_scope(Scope::Readonly)
+                    .param("alt", "media")
+                    .param("supportsAllDrives", "true")
+                    .doit();
+                let raw = Self::read_response_body(resp_obj.map(|(response, _)| response))?;
+                Self::extract_pdf_text(&raw)
+            }
+        }
+    }
+
+    /// Drains a Drive API response body into a byte buffer.
+    fn read_response_body<T>(
+        resp_obj: Result<T, google_drive3::Error>,
+    ) -> Result<Vec<u8>, SimpleError>
+    where
+        T: Read,
+    {
         let mut resp_obj = match resp_obj {
             Ok(r) => r,
             Err(e) => return Err(SimpleError::new(e.to_string())),
@@ -237,75 +479,724 @@ impl GDriveScanner {
         Ok(buffer)
     }
 
+    /// Parses a native binary spreadsheet (`.xlsx`, `.ods`) with `calamine` and flattens every
+    /// sheet's cells into tab/newline-separated text so the existing line-oriented scanning loop
+    /// can run over it exactly like an exported CSV.
+    fn extract_spreadsheet_text(raw: &[u8]) -> Result<Vec<u8>, SimpleError> {
+        let cursor = Cursor::new(raw.to_vec());
+        let mut workbook = open_workbook_auto_from_rs(cursor)
+            .map_err(|e| SimpleError::new(format!("failed parsing spreadsheet: {}", e)))?;
+
+        let mut text = String::new();
+        for sheet_name in workbook.sheet_names().to_owned() {
+            if let Some(Ok(range)) = workbook.worksheet_range(&sheet_name) {
+                for row in range.rows() {
+                    let cells: Vec<String> = row.iter().map(ToString::to_string).collect();
+                    text.push_str(&cells.join("\t"));
+                    text.push('\n');
+                }
+            }
+        }
+        Ok(text.into_bytes())
+    }
+
+    /// Extracts the text layer of a PDF with `pdf_extract`. PDFs store text inside compressed
+    /// content streams rather than as plain bytes, so scanning the raw file would almost never
+    /// surface a secret that's actually present in the document.
+    fn extract_pdf_text(raw: &[u8]) -> Result<Vec<u8>, SimpleError> {
+        let text = extract_text_from_mem(raw)
+            .map_err(|e| SimpleError::new(format!("failed extracting PDF text: {}", e)))?;
+        Ok(text.into_bytes())
+    }
+
+    /// Scans the already-downloaded `buffer` of `gdrivefile` line by line for secrets and
+    /// (optionally) high-entropy strings. Pure and synchronous so it can run on either the calling
+    /// thread ([`perform_scan`](#method.perform_scan)) or a blocking-pool thread (the concurrent
+    /// folder scan path).
+    fn scan_buffer(
+        secret_scanner: &SecretScanner,
+        gdrivefile: &GDriveFileInfo,
+        buffer: &[u8],
+        scan_entropy: bool,
+    ) -> HashSet<GDriveFinding> {
+        scan_lines_for_secrets(secret_scanner, buffer, scan_entropy, |reason, strings_found, diff| {
+            GDriveFinding {
+                diff,
+                date: gdrivefile.modified_time.clone(),
+                strings_found,
+                reason: reason.to_owned(),
+                g_drive_id: gdrivefile.file_id.to_string(),
+                path: gdrivefile.path.clone(),
+                web_link: gdrivefile.web_link.clone(),
+            }
+        })
+    }
+
     /// Takes information about the file, and the DriveHub object, and return a list of findings.
-    /// This calls get_file_contents(), so expect an HTTPS call to GDrive.
+    /// This calls `gdrive_file_contents()`, so expect an HTTPS call to GDrive. This is a single
+    /// blocking call with nothing to run concurrently against, so unlike the folder/shared-drive
+    /// scans below it stays plain synchronous code rather than spinning up a Tokio runtime for it
+    /// - and for the same reason it borrows `hub` rather than taking ownership: there's no
+    /// `spawn_blocking` task that needs to hold its own `Arc` onto it.
     pub fn perform_scan(
         &self,
         gdrivefile: &GDriveFileInfo,
-        hub: &DriveHub<
-            Client,
-            Authenticator<DefaultAuthenticatorDelegate, DiskTokenStorage, Client>,
-        >,
+        hub: &AuthenticatedDriveHub,
         scan_entropy: bool,
     ) -> HashSet<GDriveFinding> {
-        // download an export of the file, split on new lines, store in lines
         let buffer = Self::gdrive_file_contents(gdrivefile, hub).unwrap();
-        let lines = buffer.split(|x| (*x as char) == '\n');
+        Self::scan_buffer(&self.secret_scanner, gdrivefile, &buffer, scan_entropy)
+    }
 
-        // main loop - search each line for secrets, output a list of GDriveFinding objects
-        let mut findings: HashSet<GDriveFinding> = HashSet::new();
-        for new_line in lines {
-            let matches_map = self.secret_scanner.matches(&new_line);
-            for (reason, match_iterator) in matches_map {
-                let mut secrets: Vec<String> = Vec::new();
-                for matchobj in match_iterator {
-                    secrets.push(
-                        ASCII
-                            .decode(
-                                &new_line[matchobj.start()..matchobj.end()],
-                                DecoderTrap::Ignore,
-                            )
-                            .unwrap_or_else(|_| "<STRING DECODE ERROR>".parse().unwrap()),
+    /// Recursively scans an entire Google Drive folder tree, starting at `folder_id`, downloading
+    /// and scanning up to [`DEFAULT_CONCURRENCY`] files concurrently, and returns the combined set
+    /// of findings across every supported file encountered. Takes `hub` by value (rather than
+    /// borrowing, as [`perform_scan`](#method.perform_scan) does) because it's wrapped in an `Arc`
+    /// and shared across the concurrent `spawn_blocking` tasks in
+    /// [`scan_files_async`](#method.scan_files_async).
+    pub fn scan_folder(
+        &self,
+        folder_id: &str,
+        hub: AuthenticatedDriveHub,
+        scan_entropy: bool,
+    ) -> HashSet<GDriveFinding> {
+        self.scan_folder_with_concurrency(folder_id, hub, scan_entropy, DEFAULT_CONCURRENCY)
+    }
+
+    /// Same as [`scan_folder`](#method.scan_folder), but lets the caller override how many file
+    /// downloads run concurrently instead of using [`DEFAULT_CONCURRENCY`].
+    pub fn scan_folder_with_concurrency(
+        &self,
+        folder_id: &str,
+        hub: AuthenticatedDriveHub,
+        scan_entropy: bool,
+        concurrency: usize,
+    ) -> HashSet<GDriveFinding> {
+        let hub = Arc::new(hub);
+
+        // Listing is metadata-only and cheap, so it stays synchronous; the expensive part
+        // (downloading and scanning file bodies) is what gets parallelized below.
+        let mut visited: HashSet<String> = HashSet::new();
+        let mut path_cache: PathCache = HashMap::new();
+        let mut files: Vec<GDriveFileInfo> = Vec::new();
+        self.collect_folder_files(folder_id, &hub, None, &mut visited, &mut path_cache, &mut files);
+
+        let runtime = Runtime::new().expect("failed to start Tokio runtime");
+        runtime.block_on(self.scan_files_async(files, hub, scan_entropy, concurrency))
+    }
+
+    /// Scans every file in a Shared Drive (Team Drive) identified by `drive_id`. Regular folder
+    /// listing doesn't surface Shared Drive content, so this passes `includeItemsFromAllDrives`,
+    /// `corpora = "drive"`, and `driveId` on the listing call, in addition to the
+    /// `supportsAllDrives` flag threaded through every file access.
+    pub fn scan_shared_drive(
+        &self,
+        drive_id: &str,
+        hub: AuthenticatedDriveHub,
+        scan_entropy: bool,
+    ) -> HashSet<GDriveFinding> {
+        self.scan_shared_drive_with_concurrency(drive_id, hub, scan_entropy, DEFAULT_CONCURRENCY)
+    }
+
+    /// Same as [`scan_shared_drive`](#method.scan_shared_drive), but lets the caller override how
+    /// many file downloads run concurrently instead of using [`DEFAULT_CONCURRENCY`].
+    pub fn scan_shared_drive_with_concurrency(
+        &self,
+        drive_id: &str,
+        hub: AuthenticatedDriveHub,
+        scan_entropy: bool,
+        concurrency: usize,
+    ) -> HashSet<GDriveFinding> {
+        let hub = Arc::new(hub);
+        let mut visited: HashSet<String> = HashSet::new();
+        let mut path_cache: PathCache = HashMap::new();
+        let mut files: Vec<GDriveFileInfo> = Vec::new();
+        self.collect_folder_files(
+            drive_id,
+            &hub,
+            Some(drive_id),
+            &mut visited,
+            &mut path_cache,
+            &mut files,
+        );
+
+        let runtime = Runtime::new().expect("failed to start Tokio runtime");
+        runtime.block_on(self.scan_files_async(files, hub, scan_entropy, concurrency))
+    }
+
+    /// Helper for [`scan_folder_with_concurrency`](#method.scan_folder_with_concurrency) and
+    /// [`scan_shared_drive_with_concurrency`](#method.scan_shared_drive_with_concurrency) that
+    /// walks one folder level, recursing into child folders and appending every supported file
+    /// found to `files`. Pages through `hub.files().list()` using the `nextPageToken` cursor so
+    /// folders with thousands of children are handled correctly, and recurses into any child that
+    /// is itself a folder (`mimeType == "application/vnd.google-apps.folder"`). A `visited` set of
+    /// file IDs guards against cycles created by symlinked shortcuts or shared folders that
+    /// reference each other. When `drive_id` is set, the listing is scoped to that Shared Drive so
+    /// an entire Shared Drive can be scanned by ID.
+    fn collect_folder_files(
+        &self,
+        folder_id: &str,
+        hub: &AuthenticatedDriveHub,
+        drive_id: Option<&str>,
+        visited: &mut HashSet<String>,
+        path_cache: &mut PathCache,
+        files: &mut Vec<GDriveFileInfo>,
+    ) {
+        if !visited.insert(folder_id.to_owned()) {
+            return;
+        }
+        let query = format!("'{}' in parents and trashed = false", folder_id);
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut req = hub
+                .files()
+                .list()
+                .q(&query)
+                .add_scope(Scope::Readonly)
+                .param(
+                    "fields",
+                    "nextPageToken, files(id, name, mimeType, parents, shortcutDetails)",
+                )
+                .param("supportsAllDrives", "true")
+                .param("includeItemsFromAllDrives", "true");
+            if let Some(id) = drive_id {
+                // `corpora = "drive"` scopes the listing to exactly `driveId`; "allDrives" would
+                // instead search every Shared Drive the caller is a member of.
+                req = req.param("corpora", "drive").param("driveId", id);
+            }
+            if let Some(token) = &page_token {
+                req = req.page_token(token);
+            }
+            let hub_result = req.doit();
+            let (_, file_list) = match hub_result {
+                Ok(x) => x,
+                Err(e) => {
+                    eprintln!("failed listing folder {}: {:?}", folder_id, e);
+                    return;
+                }
+            };
+            for file in file_list.files.unwrap_or_else(Vec::new) {
+                let file_id = match file.id {
+                    Some(id) => id,
+                    None => continue,
+                };
+                if file.mime_type.as_deref() == Some("application/vnd.google-apps.folder") {
+                    self.collect_folder_files(
+                        &file_id,
+                        hub,
+                        drive_id,
+                        visited,
+                        path_cache,
+                        files,
                     );
+                } else if file.mime_type.as_deref() == Some("application/vnd.google-apps.shortcut")
+                {
+                    self.collect_shortcut_target(
+                        &file_id,
+                        file.shortcut_details,
+                        hub,
+                        drive_id,
+                        visited,
+                        path_cache,
+                        files,
+                    );
+                } else {
+                    match GDriveFileInfo::new(&file_id, hub, path_cache) {
+                        Ok(gdrivefile) => files.push(gdrivefile),
+                        Err(e) => eprintln!("skipping file {}: {}", file_id, e),
+                    }
                 }
-                if !secrets.is_empty() {
-                    findings.insert(GDriveFinding {
-                        diff: ASCII
-                            .decode(&new_line, DecoderTrap::Ignore)
-                            .unwrap_or_else(|_| "<STRING DECODE ERROR>".parse().unwrap()),
-                        date: gdrivefile.modified_time.clone(),
-                        strings_found: secrets.clone(),
-                        reason: reason.clone(),
-                        g_drive_id: gdrivefile.file_id.to_string(),
-                        path: gdrivefile.path.clone(),
-                        web_link: gdrivefile.web_link.clone(),
-                    });
+            }
+            page_token = file_list.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+    }
+
+    /// Resolves a Drive shortcut to its target and folds the target into `files`/recursion just
+    /// like a regular folder or file would be. `visited` guards this against shortcut cycles
+    /// (e.g. a folder shortcut pointing back at one of its own ancestors) the same way it guards
+    /// `collect_folder_files` against symlink-style folder cycles.
+    #[allow(clippy::too_many_arguments)]
+    fn collect_shortcut_target(
+        &self,
+        shortcut_id: &str,
+        shortcut_details: Option<google_drive3::api::FileShortcutDetails>,
+        hub: &AuthenticatedDriveHub,
+        drive_id: Option<&str>,
+        visited: &mut HashSet<String>,
+        path_cache: &mut PathCache,
+        files: &mut Vec<GDriveFileInfo>,
+    ) {
+        let details = match shortcut_details {
+            Some(d) => d,
+            None => {
+                eprintln!("shortcut {} has no shortcutDetails, skipping", shortcut_id);
+                return;
+            }
+        };
+        let target_id = match details.target_id {
+            Some(id) => id,
+            None => {
+                eprintln!("shortcut {} has no targetId, skipping", shortcut_id);
+                return;
+            }
+        };
+        if details.target_mime_type.as_deref() == Some("application/vnd.google-apps.folder") {
+            self.collect_folder_files(&target_id, hub, drive_id, visited, path_cache, files);
+        } else {
+            if !visited.insert(target_id.clone()) {
+                return;
+            }
+            match GDriveFileInfo::new(&target_id, hub, path_cache) {
+                Ok(gdrivefile) => files.push(gdrivefile),
+                Err(e) => eprintln!("skipping shortcut target {}: {}", target_id, e),
+            }
+        }
+    }
+
+    /// Downloads and scans `files` with up to `concurrency` downloads in flight at once. Each
+    /// download plus its CPU-bound regex matching runs via `spawn_blocking` on Tokio's blocking
+    /// thread pool, since the underlying `DriveHub` calls and `SecretScanner::matches` are both
+    /// synchronous; `buffer_unordered` caps how many of those blocking tasks are outstanding at
+    /// once so a folder of thousands of files doesn't spawn thousands of threads at once.
+    async fn scan_files_async(
+        &self,
+        files: Vec<GDriveFileInfo>,
+        hub: Arc<AuthenticatedDriveHub>,
+        scan_entropy: bool,
+        concurrency: usize,
+    ) -> HashSet<GDriveFinding> {
+        let secret_scanner = self.secret_scanner.clone();
+        let results: Vec<HashSet<GDriveFinding>> = stream::iter(files)
+            .map(|gdrivefile| {
+                let hub = Arc::clone(&hub);
+                let secret_scanner = secret_scanner.clone();
+                task::spawn_blocking(move || {
+                    let buffer = match Self::gdrive_file_contents(&gdrivefile, &hub) {
+                        Ok(b) => b,
+                        Err(e) => {
+                            eprintln!("failed downloading {}: {}", gdrivefile.file_id, e);
+                            return HashSet::new();
+                        }
+                    };
+                    Self::scan_buffer(&secret_scanner, &gdrivefile, &buffer, scan_entropy)
+                })
+            })
+            .buffer_unordered(concurrency)
+            .map(|task_result| {
+                task_result.unwrap_or_else(|e| {
+                    eprintln!("scan task panicked: {:?}", e);
+                    HashSet::new()
+                })
+            })
+            .collect()
+            .await;
+
+        let mut findings: HashSet<GDriveFinding> = HashSet::new();
+        for result in results {
+            findings.extend(result);
+        }
+        findings
+    }
+}
+
+impl Default for GDriveScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+/// `serde_json` object that represents a single found secret - finding
+///
+/// ```
+/// # use rusty_hogs::google_scanning::GCSFinding;
+/// let gcsf: GCSFinding = GCSFinding {
+///    bucket: String::from("my-bucket"),
+///    object_name: String::from("path/to/object.csv"),
+///    generation: String::from("1234567890"),
+///    content_type: String::from("text/csv"),
+///    diff: String::from("context around finding"),
+///    strings_found: Vec::new(),
+///    reason: String::from("Regex description"),
+/// };
+/// ```
+pub struct GCSFinding {
+    pub bucket: String,
+    pub object_name: String,
+    pub generation: String,
+    pub content_type: String,
+    pub diff: String,
+    #[serde(rename = "stringsFound")]
+    pub strings_found: Vec<String>,
+    pub reason: String,
+}
+
+/// Acts as a wrapper around a `SecretScanner` object to provide helper functions for performing
+/// scanning against Google Cloud Storage objects. Relies on the
+/// [`google_storage1`](https://docs.rs/google-storage1/1.0.10+20190624/google_storage1/) library
+/// which provides a wrapper around the Google Cloud Storage JSON API.
+///
+/// ```
+/// # use rusty_hogs::google_scanning::GCSScanner;
+/// let gcss: GCSScanner = GCSScanner::new();
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct GCSScanner {
+    pub secret_scanner: SecretScanner,
+}
+
+impl GCSScanner {
+    /// Initialize the `SecretScanner` object first using the `SecretScannerBuilder`, then provide
+    /// it to this constructor method.
+    pub fn new_from_scanner(secret_scanner: SecretScanner) -> Self {
+        Self { secret_scanner }
+    }
+
+    pub fn new() -> Self {
+        Self {
+            secret_scanner: SecretScanner::default(),
+        }
+    }
+
+    /// Downloads the bytes of a single object from a bucket via `objects.get` with `alt=media`.
+    fn gcs_object_contents(
+        bucket: &str,
+        object_name: &str,
+        hub: &Storage<Client, Authenticator<DefaultAuthenticatorDelegate, DiskTokenStorage, Client>>,
+    ) -> Result<Vec<u8>, SimpleError> {
+        let resp_obj = hub
+            .objects()
+            .get(bucket, object_name)
+            .param("alt", "media")
+            .add_scope(StorageScope::DevstorageReadOnly)
+            .doit();
+        let mut resp_obj = match resp_obj {
+            Ok((r, _)) => r,
+            Err(e) => return Err(SimpleError::new(e.to_string())),
+        };
+        let mut buffer: Vec<u8> = Vec::new();
+        match resp_obj.read_to_end(&mut buffer) {
+            Err(e) => return Err(SimpleError::new(e.to_string())),
+            Ok(s) => s,
+        };
+        Ok(buffer)
+    }
+
+    /// Downloads and scans a single object, returning every finding within it. This calls
+    /// `gcs_object_contents()`, so expect an HTTPS call to GCS.
+    fn scan_object(
+        &self,
+        bucket: &str,
+        object_name: &str,
+        generation: &str,
+        content_type: &str,
+        hub: &Storage<Client, Authenticator<DefaultAuthenticatorDelegate, DiskTokenStorage, Client>>,
+        scan_entropy: bool,
+    ) -> HashSet<GCSFinding> {
+        let buffer = match Self::gcs_object_contents(bucket, object_name, hub) {
+            Ok(b) => b,
+            Err(e) => {
+                eprintln!("failed downloading {}/{}: {}", bucket, object_name, e);
+                return HashSet::new();
+            }
+        };
+        scan_lines_for_secrets(
+            &self.secret_scanner,
+            &buffer,
+            scan_entropy,
+            |reason, strings_found, diff| GCSFinding {
+                bucket: bucket.to_owned(),
+                object_name: object_name.to_owned(),
+                generation: generation.to_owned(),
+                content_type: content_type.to_owned(),
+                diff,
+                strings_found,
+                reason: reason.to_owned(),
+            },
+        )
+    }
+
+    /// Lists every object in `bucket` (paginating on `nextPageToken` the way [`scan_folder`] pages
+    /// through Drive) and scans each one for secrets, returning the combined set of findings.
+    ///
+    /// [`scan_folder`]: struct.GDriveScanner.html#method.scan_folder
+    pub fn perform_scan(
+        &self,
+        bucket: &str,
+        hub: &Storage<Client, Authenticator<DefaultAuthenticatorDelegate, DiskTokenStorage, Client>>,
+        scan_entropy: bool,
+    ) -> HashSet<GCSFinding> {
+        let mut findings: HashSet<GCSFinding> = HashSet::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut req = hub
+                .objects()
+                .list(bucket)
+                .add_scope(StorageScope::DevstorageReadOnly);
+            if let Some(token) = &page_token {
+                req = req.page_token(token);
+            }
+            let hub_result = req.doit();
+            let (_, object_list) = match hub_result {
+                Ok(x) => x,
+                Err(e) => {
+                    eprintln!("failed listing bucket {}: {:?}", bucket, e);
+                    break;
                 }
+            };
+            for object in object_list.items.unwrap_or_else(Vec::new) {
+                let object_name = match &object.name {
+                    Some(name) => name.clone(),
+                    None => continue,
+                };
+                let generation = object.generation.clone().unwrap_or_default();
+                let content_type = object.content_type.clone().unwrap_or_default();
+                findings.extend(self.scan_object(
+                    bucket,
+                    &object_name,
+                    &generation,
+                    &content_type,
+                    hub,
+                    scan_entropy,
+                ));
+            }
+            page_token = object_list.next_page_token;
+            if page_token.is_none() {
+                break;
             }
+        }
+        findings
+    }
+}
+
+impl Default for GCSScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+/// `serde_json` object that represents a single found secret - finding
+///
+/// ```
+/// # use rusty_hogs::google_scanning::GmailFinding;
+/// let gf: GmailFinding = GmailFinding {
+///    message_id: String::from("16e1b2c3d4e5f6a7"),
+///    thread_id: String::from("16e1b2c3d4e5f6a7"),
+///    from: String::from("someone@example.com"),
+///    subject: String::from("email subject"),
+///    date: String::from("Mon, 1 Jan 2020 00:00:00 +0000"),
+///    diff: String::from("context around finding"),
+///    strings_found: Vec::new(),
+///    reason: String::from("Regex description"),
+/// };
+/// ```
+pub struct GmailFinding {
+    pub message_id: String,
+    pub thread_id: String,
+    pub from: String,
+    pub subject: String,
+    pub date: String,
+    pub diff: String,
+    #[serde(rename = "stringsFound")]
+    pub strings_found: Vec<String>,
+    pub reason: String,
+}
+
+/// Acts as a wrapper around a `SecretScanner` object to provide helper functions for performing
+/// scanning against a user's Gmail messages and attachments. Relies on the
+/// [`google_gmail1`](https://docs.rs/google-gmail1/1.0.10+20190620/google_gmail1/) library which
+/// provides a wrapper around the Gmail API.
+///
+/// ```
+/// # use rusty_hogs::google_scanning::GmailScanner;
+/// let gms: GmailScanner = GmailScanner::new();
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct GmailScanner {
+    pub secret_scanner: SecretScanner,
+}
+
+impl GmailScanner {
+    /// Initialize the `SecretScanner` object first using the `SecretScannerBuilder`, then provide
+    /// it to this constructor method.
+    pub fn new_from_scanner(secret_scanner: SecretScanner) -> Self {
+        Self { secret_scanner }
+    }
 
-            if scan_entropy {
-                let ef = SecretScanner::entropy_findings(new_line);
-                if !ef.is_empty() {
-                    findings.insert(GDriveFinding {
-                        diff: ASCII
-                            .decode(&new_line, DecoderTrap::Ignore)
-                            .unwrap_or_else(|_| "<STRING DECODE ERROR>".parse().unwrap()),
-                        date: gdrivefile.modified_time.clone(),
-                        strings_found: ef,
-                        reason: "Entropy".parse().unwrap(),
-                        g_drive_id: gdrivefile.file_id.to_string(),
-                        path: gdrivefile.path.clone(),
-                        web_link: gdrivefile.web_link.clone(),
-                    });
+    pub fn new() -> Self {
+        Self {
+            secret_scanner: SecretScanner::default(),
+        }
+    }
+
+    /// Recursively walks a message's MIME tree, decoding each text part's inline `body.data` and
+    /// fetching each attachment part's bytes via `users.messages.attachments.get`, appending the
+    /// decoded bytes of every part onto `chunks`.
+    fn collect_part_bytes(
+        user_id: &str,
+        message_id: &str,
+        part: &MessagePart,
+        hub: &Gmail<Client, Authenticator<DefaultAuthenticatorDelegate, DiskTokenStorage, Client>>,
+        chunks: &mut Vec<Vec<u8>>,
+    ) {
+        if let Some(body) = &part.body {
+            if let Some(data) = &body.data {
+                // The Gmail API returns body.data as unpadded base64url.
+                match decode_config(data, URL_SAFE_NO_PAD) {
+                    Ok(decoded) => chunks.push(decoded),
+                    Err(e) => eprintln!(
+                        "failed decoding body of message {}: {}",
+                        message_id, e
+                    ),
                 }
+            } else if let Some(attachment_id) = &body.attachment_id {
+                let hub_result = hub
+                    .users()
+                    .messages_attachments_get(user_id, message_id, attachment_id)
+                    .add_scope(GmailScope::Readonly)
+                    .doit();
+                match hub_result {
+                    Ok((_, attachment)) => {
+                        if let Some(data) = attachment.data {
+                            match decode_config(&data, URL_SAFE_NO_PAD) {
+                                Ok(decoded) => chunks.push(decoded),
+                                Err(e) => eprintln!(
+                                    "failed decoding attachment {} of message {}: {}",
+                                    attachment_id, message_id, e
+                                ),
+                            }
+                        }
+                    }
+                    Err(e) => eprintln!(
+                        "failed fetching attachment {} of message {}: {:?}",
+                        attachment_id, message_id, e
+                    ),
+                }
+            }
+        }
+        if let Some(parts) = &part.parts {
+            for child in parts {
+                Self::collect_part_bytes(user_id, message_id, child, hub, chunks);
             }
         }
+    }
+
+    /// Fetches a single message in `full` format and scans its decoded body and attachment bytes
+    /// for secrets.
+    fn scan_message(
+        &self,
+        user_id: &str,
+        message_id: &str,
+        hub: &Gmail<Client, Authenticator<DefaultAuthenticatorDelegate, DiskTokenStorage, Client>>,
+        scan_entropy: bool,
+    ) -> HashSet<GmailFinding> {
+        let hub_result = hub
+            .users()
+            .messages_get(user_id, message_id)
+            .param("format", "full")
+            .add_scope(GmailScope::Readonly)
+            .doit();
+        let (_, message) = match hub_result {
+            Ok(x) => x,
+            Err(e) => {
+                eprintln!("failed fetching message {}: {:?}", message_id, e);
+                return HashSet::new();
+            }
+        };
+        let thread_id = message.thread_id.clone().unwrap_or_default();
+        let headers = message
+            .payload
+            .as_ref()
+            .and_then(|p| p.headers.clone())
+            .unwrap_or_else(Vec::new);
+        let header_value = |name: &str| -> String {
+            headers
+                .iter()
+                .find(|h| h.name.as_deref() == Some(name))
+                .and_then(|h| h.value.clone())
+                .unwrap_or_default()
+        };
+        let from = header_value("From");
+        let subject = header_value("Subject");
+        let date = header_value("Date");
 
-        HashSet::from_iter(findings.into_iter())
+        let mut chunks: Vec<Vec<u8>> = Vec::new();
+        if let Some(payload) = &message.payload {
+            Self::collect_part_bytes(user_id, message_id, payload, hub, &mut chunks);
+        }
+
+        let mut findings: HashSet<GmailFinding> = HashSet::new();
+        for chunk in &chunks {
+            findings.extend(scan_lines_for_secrets(
+                &self.secret_scanner,
+                chunk,
+                scan_entropy,
+                |reason, strings_found, diff| GmailFinding {
+                    message_id: message_id.to_owned(),
+                    thread_id: thread_id.clone(),
+                    from: from.clone(),
+                    subject: subject.clone(),
+                    date: date.clone(),
+                    diff,
+                    strings_found,
+                    reason: reason.to_owned(),
+                },
+            ));
+        }
+        findings
+    }
+
+    /// Enumerates a user's messages via `users.messages.list` (optionally filtered by `query`,
+    /// e.g. `"has:attachment"`), paginating on `nextPageToken`, and scans each one for secrets.
+    pub fn perform_scan(
+        &self,
+        user_id: &str,
+        hub: &Gmail<Client, Authenticator<DefaultAuthenticatorDelegate, DiskTokenStorage, Client>>,
+        query: Option<&str>,
+        scan_entropy: bool,
+    ) -> HashSet<GmailFinding> {
+        let mut findings: HashSet<GmailFinding> = HashSet::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut req = hub
+                .users()
+                .messages_list(user_id)
+                .add_scope(GmailScope::Readonly);
+            if let Some(q) = query {
+                req = req.q(q);
+            }
+            if let Some(token) = &page_token {
+                req = req.page_token(token);
+            }
+            let hub_result = req.doit();
+            let (_, message_list) = match hub_result {
+                Ok(x) => x,
+                Err(e) => {
+                    eprintln!("failed listing messages for {}: {:?}", user_id, e);
+                    break;
+                }
+            };
+            for message_ref in message_list.messages.unwrap_or_else(Vec::new) {
+                let message_id = match message_ref.id {
+                    Some(id) => id,
+                    None => continue,
+                };
+                findings.extend(self.scan_message(user_id, &message_id, hub, scan_entropy));
+            }
+            page_token = message_list.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+        findings
     }
 }
 
-impl Default for GDriveScanner {
+impl Default for GmailScanner {
     fn default() -> Self {
         Self::new()
     }